@@ -8,18 +8,33 @@
       MIN_FPS:           1,
       MAX_FPS:           60,
       DEFAULT_FPS:       20,
-      QR_WIDTH:          350,
+      QR_WIDTH:          350,  // max on-screen QR box, CSS px
       QR_ERROR_LEVEL:    'L',
+      QR_MAX_BYTES:      { L: 2953, M: 2331, Q: 1663, H: 1273 },  // version-40 byte-mode capacity per EC level
+      QRAM_HEADER_BYTES: 81,   // qram packet header, plus 2 per block index (README, Wire Format)
+      QR_MARGIN:         1,    // quiet zone, in modules
+      MAX_BLOCKS_PER_PACKET: 50,  // qram's default degree cap, needed for the packet-size bound
+      PAUSE_HISTORY:     64,   // recent frames kept for stepping while paused
 
       // Decoder scan
       CROP_FRACTION:     0.85,
       DOWNSCALE_PX:      480,
+      DECODE_OVERHEAD:   1.2,  // typical packets needed per block (LT overhead)
+      BLOCK_MAP_COLS:    200,  // block map cells per row
+      INVERT_PROBE_FRAMES: 30, // empty frames in a row before also trying inverted codes
+      VIDEO_SCAN_PX:     960,  // recorded video: longest side of the scanned frame
+      VIDEO_PLAYBACK_RATE: 0.5,  // slow playback so fewer recorded frames are skipped
 
       // UI timing
       FLASH_DURATION_MS:       150,
       COPY_CONFIRM_MS:         2000,
       SPEED_UPDATE_INTERVAL:   500,
 
+      // Receiver stall handling
+      STALL_HINT_MS:           5000,    // no new packets → show "move closer" hint
+      REJECT_HINT_COUNT:       10,      // consecutive rejects → show "sender changed" hint
+      SCAN_TIMEOUT_MS:         120000,  // no new packets → stop the camera
+
       // File protocol magic: "QRAMF"
       FILE_MAGIC: new Uint8Array([0x51, 0x52, 0x41, 0x4D, 0x46]),
     });
@@ -69,6 +84,7 @@
       const elBlk      = document.getElementById('blk');
       const elAutoBlk  = document.getElementById('auto-blk');
       const elCompress = document.getElementById('compress');
+      const elInvert   = document.getElementById('invert');
       const elStart    = document.getElementById('start');
       const elStop     = document.getElementById('stop');
       const elStats    = document.getElementById('stats');
@@ -86,6 +102,14 @@
       let reader = null;
       let stream = null;
 
+      // Pause / step state. The qram encoder picks packet degrees with
+      // Math.random, so frames cannot be regenerated — instead the last
+      // PAUSE_HISTORY rendered packets are kept and replayed while paused.
+      let paused = false;
+      let resumeWaiter = null;
+      let history = [];
+      let historyPos = -1;
+
       let currentMode = 'text';
       let loadedFile = null;
 
@@ -167,6 +191,7 @@
       });
 
       // --- Auto block size ---
+      // Size tiers tuned for scan reliability.
       function autoBlockSize(dataLength) {
         if (dataLength <= 50)     return 50;
         if (dataLength <= 600)    return dataLength;
@@ -177,6 +202,41 @@
         return 700;
       }
 
+      // Block size and FPS as start() will use them, clamped to CONFIG limits.
+      function currentBlockSize() {
+        return Math.max(CONFIG.MIN_BLOCK_SIZE, Math.min(CONFIG.MAX_BLOCK_SIZE, parseInt(elBlk.value, 10) || CONFIG.DEFAULT_BLOCK));
+      }
+
+      function currentFps() {
+        return Math.max(CONFIG.MIN_FPS, Math.min(CONFIG.MAX_FPS, parseInt(elFPS.value, 10) || CONFIG.DEFAULT_FPS));
+      }
+
+      // Predicted transfer shape for `size` wire bytes at the current
+      // block size and FPS, e.g. ", 12 blocks, ~15 frames (~1s)".
+      function transferEstimate(size) {
+        const blockSize = currentBlockSize();
+        const fps       = currentFps();
+        const blocks    = Math.ceil(size / blockSize);
+        const frames    = Math.ceil(blocks * CONFIG.DECODE_OVERHEAD);
+        return `, ${blocks} block${blocks === 1 ? '' : 's'}, ~${frames} frames (~${Math.ceil(frames / fps)}s)`;
+      }
+
+      // --- QR capacity ---
+      const qrMaxBytes = CONFIG.QR_MAX_BYTES[CONFIG.QR_ERROR_LEVEL];
+
+      // Worst-case packet size for this payload and block size.
+      function maxPacketBytes(dataLength, blockSize) {
+        const degree = Math.min(Math.ceil(dataLength / blockSize), CONFIG.MAX_BLOCKS_PER_PACKET);
+        return CONFIG.QRAM_HEADER_BYTES + 2 * degree + blockSize;
+      }
+
+      // Largest block size whose worst-case packet still fits in one QR code.
+      function maxBlockSize(dataLength) {
+        let b = qrMaxBytes - CONFIG.QRAM_HEADER_BYTES - 2;
+        while (b > CONFIG.MIN_BLOCK_SIZE && maxPacketBytes(dataLength, b) > qrMaxBytes) b--;
+        return b;
+      }
+
       elAutoBlk.addEventListener('change', () => {
         elBlk.disabled = elAutoBlk.checked;
         if (elAutoBlk.checked) updateDataSize();
@@ -185,8 +245,18 @@
       elBlk.disabled = elAutoBlk.checked;
       elTxt.addEventListener('input', updateDataSize);
       elCompress.addEventListener('change', updateDataSize);
+      elBlk.addEventListener('input', renderDataSize);
+      elFPS.addEventListener('input', renderDataSize);
 
       let _compressPreviewTimer = null;
+      let _compressPreviewSeq   = 0;
+      let _compressPreview      = null;  // { source, cr } — last maybeCompress result
+      let _dataSize             = 0;
+
+      // Identifies the current payload: the text itself, or the loaded file.
+      function payloadSource() {
+        return currentMode === 'text' ? elTxt.value : loadedFile;
+      }
 
       function updateDataSize() {
         let size = 0;
@@ -197,35 +267,49 @@
           const nameBytes = new TextEncoder().encode(loadedFile.name).length;
           size = FILE_MAGIC.length + 2 + nameBytes + loadedFile.data.length;
         }
+        _dataSize = size;
 
-        if (size > 0) {
-          elDataSize.textContent = `Data: ${formatBytes(size)}`;
-          if (elAutoBlk.checked) elBlk.value = autoBlockSize(size);
-          // Show compression preview (debounced for text input)
-          if (elCompress.checked && window.qramCompress) {
-            if (currentMode === 'text') {
-              clearTimeout(_compressPreviewTimer);
-              _compressPreviewTimer = setTimeout(() => updateCompressionPreview(size), 300);
-            } else {
-              updateCompressionPreview(size);
-            }
+        if (size > 0 && elAutoBlk.checked) elBlk.value = autoBlockSize(size);
+        renderDataSize();
+
+        // Refresh the compression preview when the payload changed (debounced for text input)
+        if (size > 0 && elCompress.checked && window.qramCompress &&
+            _compressPreview?.source !== payloadSource()) {
+          clearTimeout(_compressPreviewTimer);
+          if (currentMode === 'text') {
+            _compressPreviewTimer = setTimeout(updateCompressionPreview, 300);
+          } else {
+            updateCompressionPreview();
           }
+        }
+      }
+
+      // Redraw the size line from the last known size and compression result;
+      // block size / FPS edits only change the estimate, so nothing is recompressed.
+      function renderDataSize() {
+        if (_dataSize === 0) { elDataSize.textContent = ''; return; }
+        const cr = elCompress.checked && _compressPreview?.source === payloadSource()
+          ? _compressPreview.cr : null;
+        if (!cr) {
+          elDataSize.textContent = `Data: ${formatBytes(_dataSize)}${transferEstimate(_dataSize)}`;
+        } else if (cr.compressed) {
+          const pct = ((1 - cr.sentSize / cr.originalSize) * 100).toFixed(0);
+          elDataSize.textContent = `Data: ${formatBytes(cr.originalSize)} → ${formatBytes(cr.sentSize)} (gz, −${pct}%)${transferEstimate(cr.sentSize)}`;
         } else {
-          elDataSize.textContent = '';
+          elDataSize.textContent = `Data: ${formatBytes(cr.sentSize)} (won't compress)${transferEstimate(cr.sentSize)}`;
         }
       }
 
-      async function updateCompressionPreview(rawSize) {
+      async function updateCompressionPreview() {
+        const seq     = ++_compressPreviewSeq;
+        const source  = payloadSource();
         const payload = buildPayload();
         if (!payload || payload.length === 0) return;
         try {
-          const cr = await qramCompress.maybeCompress(payload);
-          if (cr.compressed) {
-            const pct = ((1 - cr.sentSize / cr.originalSize) * 100).toFixed(0);
-            elDataSize.textContent = `Data: ${formatBytes(cr.originalSize)} → ${formatBytes(cr.sentSize)} (gz, −${pct}%)`;
-          } else {
-            elDataSize.textContent = `Data: ${formatBytes(cr.sentSize)} (won't compress)`;
-          }
+          const { compressed, originalSize, sentSize } = await qramCompress.maybeCompress(payload);
+          if (seq !== _compressPreviewSeq) return;  // superseded by a newer payload
+          _compressPreview = { source, cr: { compressed, originalSize, sentSize } };
+          renderDataSize();
         } catch (_) {}
       }
 
@@ -256,6 +340,7 @@
       }
 
       function clearCanvas() {
+        frame = null;
         const ctx = elCanvas.getContext('2d');
         ctx.save();
         ctx.setTransform(1, 0, 0, 1, 0, 0);
@@ -310,13 +395,25 @@
           sendData = cr.data;
         }
 
-        const fps       = Math.max(CONFIG.MIN_FPS, Math.min(CONFIG.MAX_FPS, parseInt(elFPS.value, 10) || CONFIG.DEFAULT_FPS));
-        const blockSize = Math.max(CONFIG.MIN_BLOCK_SIZE, Math.min(CONFIG.MAX_BLOCK_SIZE, parseInt(elBlk.value, 10) || CONFIG.DEFAULT_BLOCK));
+        const fps       = currentFps();
+        const blockSize = currentBlockSize();
         const delay     = 1000 / fps;
 
+        const packetBytes = maxPacketBytes(sendData.length, blockSize);
+        if (packetBytes > qrMaxBytes) {
+          showError(`Block size ${blockSize} is too large: packets can reach ${packetBytes} bytes, ` +
+                    `but a QR code at EC level ${CONFIG.QR_ERROR_LEVEL} holds at most ${qrMaxBytes}. ` +
+                    `Use a block size of ${maxBlockSize(sendData.length)} or less.`);
+          return;
+        }
+
         let enc;
         try {
-          enc = new qram.Encoder({ data: sendData, blockSize });
+          enc = new qram.Encoder({
+            data: sendData,
+            blockSize,
+            maxBlocksPerPacket: CONFIG.MAX_BLOCKS_PER_PACKET,
+          });
         } catch (e) {
           showError('Failed to create qram.Encoder (bad options?)', e);
           return;
@@ -335,6 +432,7 @@
         setEncodeView(true);
 
         let n = 0;
+        let endReason = 'stopped';
         const blocks = Math.ceil(sendData.length / blockSize);
         const modeLabel = currentMode === 'file' ? `file: ${loadedFile.name}, ` : '';
         elStats.textContent = `${modeLabel}${formatBytes(sendData.length)}, ${blocks} blocks`;
@@ -349,45 +447,174 @@
 
         try {
           while (!cancelRequested) {
+            if (paused) {
+              await new Promise(ok => { resumeWaiter = ok; });
+              continue;
+            }
+
             qramPerf.start('encode-frame');
             const { value: pkt, done } = await reader.read();
-            if (done) { qramPerf.end('encode-frame'); break; }
+            if (done) { qramPerf.end('encode-frame'); endReason = cancelRequested ? 'stopped' : 'stream-ended'; break; }
+
+            history.push(pkt.data);
+            if (history.length > CONFIG.PAUSE_HISTORY) history.shift();
 
             try {
               qramPerf.start('qr-render');
-              await QRCode.toCanvas(elCanvas, [{ data: pkt.data, mode: 'byte' }], {
-                width: CONFIG.QR_WIDTH,
-                margin: 1,
-                errorCorrectionLevel: CONFIG.QR_ERROR_LEVEL,
-              });
+              await renderPacket(pkt.data);
               qramPerf.end('qr-render');
             } catch (e) {
               qramPerf.end('qr-render');
-              showError('QR render error (QRCode.toCanvas failed).', e);
+              showError('QR render error.', e);
+              endReason = 'render-error';
               break;
             }
 
             n++;
-            elStats.textContent = `${modeLabel}${formatBytes(sendData.length)}, ${blocks} blocks, packet #${n}`;
+            if (paused) {
+              // Paused while this frame was rendering — it is now the one on screen.
+              historyPos = history.length - 1;
+              showPausedStats();
+            } else {
+              elStats.textContent = `${modeLabel}${formatBytes(sendData.length)}, ${blocks} blocks, packet #${n}`;
+            }
             qramPerf.end('encode-frame');
             await new Promise(ok => setTimeout(ok, delay));
           }
         } catch (e) {
           showError('Streaming loop error.', e);
+          endReason = 'error';
         } finally {
-          qramPerf.sessionEnd({ packetsSent: n });
+          qramPerf.sessionEnd({
+            endReason,
+            packetsSent: n,
+            // Frames shown per source block; compare with the receiver's overheadRatio.
+            sendRatio: +(n / blocks).toFixed(2),
+          });
           try { await reader?.cancel(); } catch (e) {}
           try { await stream?.cancel?.(); } catch (e) {}
           reader = null;
           stream = null;
           running = false;
           cancelRequested = false;
+          paused = false;
+          resumeWaiter = null;
+          history = [];
+          historyPos = -1;
           setEncodeView(false);
         }
       }
 
+      // Inverted output (light modules on dark) suits dark-mode displays; the
+      // quiet zone takes the dark colour so it still contrasts with the code.
+      //
+      // The backing store matches the canvas's on-screen box (at most QR_WIDTH
+      // CSS px) in *device* pixels, so CSS only scales it by dpr. The code is
+      // drawn centred at a whole number of pixels per module, and the leftover
+      // becomes extra quiet zone, so module edges stay sharp on HiDPI and
+      // fractionally scaled displays.
+      // Rasterize modules into one ImageData reused across frames (node-qrcode's
+      // toCanvas allocated a new one per frame), then blit it with a single
+      // putImageData. Only modules that changed since the last frame are
+      // rewritten in the buffer, as horizontal runs of 32-bit pixels.
+      let frame = null;  // { key, img, words, bits } — cleared by clearCanvas()
+
+      async function renderPacket(data) {
+        const qr      = QRCode.create([{ data, mode: 'byte' }],
+                                      { errorCorrectionLevel: CONFIG.QR_ERROR_LEVEL });
+        const size    = qr.modules.size;
+        const bits    = qr.modules.data;
+        const modules = size + 2 * CONFIG.QR_MARGIN;
+        const dpr     = window.devicePixelRatio || 1;
+        const boxCss  = elCanvas.getBoundingClientRect().width ||
+                        Math.min(CONFIG.QR_WIDTH, elCanvas.parentElement.clientWidth || CONFIG.QR_WIDTH);
+        const boxPx   = Math.round(boxCss * dpr);
+        const scale   = Math.max(1, Math.floor(boxPx / modules));
+        const px      = Math.max(boxPx, modules * scale);  // wider only if the box can't fit 1 px/module
+        const [dark, light] = elInvert.checked ? [WHITE, BLACK] : [BLACK, WHITE];
+
+        const key = `${px}|${size}|${dark}`;
+        if (frame?.key !== key) {
+          elCanvas.width = elCanvas.height = px;
+          const img = new ImageData(px, px);
+          frame = { key, img, words: new Uint32Array(img.data.buffer), bits: null };
+          frame.words.fill(light);
+        }
+        const { words } = frame;
+        const prev = frame.bits;
+
+        const off = Math.floor((px - modules * scale) / 2) + CONFIG.QR_MARGIN * scale;
+        for (let r = 0; r < size; r++) {
+          const row = r * size;
+          let c = 0;
+          while (c < size) {
+            const bit = bits[row + c];
+            if (prev && prev[row + c] === bit) { c++; continue; }
+            const c0 = c;
+            while (c < size && bits[row + c] === bit && !(prev && prev[row + c] === bit)) c++;
+            const x0 = off + c0 * scale, x1 = off + c * scale;
+            for (let y = off + r * scale, yEnd = y + scale; y < yEnd; y++) {
+              words.fill(bit ? dark : light, y * px + x0, y * px + x1);
+            }
+          }
+        }
+        frame.bits = bits;
+        elCanvas.getContext('2d').putImageData(frame.img, 0, 0);
+      }
+
+      // Opaque black/white as one RGBA pixel, in the platform's byte order.
+      function rgbaWord(v) {
+        return new Uint32Array(new Uint8ClampedArray([v, v, v, 255]).buffer)[0];
+      }
+      const BLACK = rgbaWord(0);
+      const WHITE = rgbaWord(255);
+
+      // --- Pause / step ---
+      function showPausedStats() {
+        elStats.textContent = `Paused — frame ${historyPos + 1}/${history.length} (←/→ step, tap to resume)`;
+      }
+
+      function togglePause() {
+        if (!running) return;
+        paused = !paused;
+        if (paused) {
+          historyPos = history.length - 1;
+          showPausedStats();
+        } else {
+          historyPos = -1;
+          resumeWaiter?.();
+          resumeWaiter = null;
+        }
+      }
+
+      async function step(delta) {
+        if (!running || !paused || !history.length) return;
+        const next = Math.max(0, Math.min(history.length - 1, historyPos + delta));
+        if (next === historyPos) return;
+        historyPos = next;
+        try {
+          await renderPacket(history[historyPos]);
+          showPausedStats();
+        } catch (e) {
+          showError('QR render error.', e);
+        }
+      }
+
+      elCanvas.addEventListener('click', togglePause);
+
+      document.addEventListener('keydown', e => {
+        if (!running) return;
+        // Leave keys alone on focused controls (Space activates Stop, arrows edit inputs).
+        if (e.target.closest?.('button, input, textarea, select')) return;
+        if (e.key === ' ')               { e.preventDefault(); togglePause(); }
+        else if (e.key === 'ArrowLeft')  { e.preventDefault(); step(-1); }
+        else if (e.key === 'ArrowRight') { e.preventDefault(); step(1); }
+      });
+
       function stop() {
         cancelRequested = true;
+        resumeWaiter?.();
+        resumeWaiter = null;
         try { reader?.cancel(); } catch (e) {}
         try { stream?.cancel?.(); } catch (e) {}
       }
@@ -411,6 +638,8 @@
     const ctx            = canvas.getContext('2d', { willReadFrequently: true });
     const statusEl       = document.getElementById('status');
     const progressFill   = document.getElementById('progress-fill');
+    const blockMap       = document.getElementById('block-map');
+    const blockMapCtx    = blockMap.getContext('2d');
     const blocksReceivedEl  = document.getElementById('blocks-received');
     const blocksTotalEl     = document.getElementById('blocks-total');
     const packetsScannedEl  = document.getElementById('packets-scanned');
@@ -429,10 +658,14 @@
     const errorMsg     = document.getElementById('error-msg');
     const scanIndicator = document.getElementById('scan-indicator');
     const decScan      = document.getElementById('dec-scan');
+    const videoContainer = document.getElementById('video-container');
+    const videoFileBtn   = document.getElementById('video-file-btn');
+    const videoFileInput = document.getElementById('video-file-input');
 
     // State
     let scanning = true;
     let cameraStream = null;
+    let videoFileUrl = null;   // object URL while scanning a recorded video
     let decoder = null;
     let decodePromise = null;
     let packetsScanned = 0;
@@ -440,16 +673,30 @@
     let lastPacketSignature = null;
     let speedInterval = null;
     let pendingProgressUpdate = false;
+    // ZXing's inversion pass doubles the cost of a frame, and inverted output
+    // is an encoder opt-in, so it only runs once INVERT_PROBE_FRAMES frames in
+    // a row have read nothing, or while the last code read was inverted.
+    let emptyFrames = 0;
+    let lastInverted = false;
     // Generation counter — incremented each time initCamera starts a new scan
     // loop. The loop closure captures its own gen; stale callbacks bail out
     // when scanGen advances, preventing double-loop races on reset/reinit.
     let scanGen = 0;
 
+    // Stall watchdog
+    let watchdogInterval = null;
+    let lastActivityTime = 0;
+    let rejectsSinceAccept = 0;
+
+    // Rejected packets by reason, e.g. { truncated: 3, checksum: 1 }
+    let rejectCounts = {};
+
     // Speed tracking
     let firstPacketTime = null;
     let lastReceivedBlocks = 0;
     let lastTotalBlocks = 0;
     let totalBytesReceived = 0;
+    let lastProgress = null;   // qram progress: { blockSize, totalSize, ... }
 
     // File transfer state
     let decodedFileData = null;
@@ -552,13 +799,126 @@
       }
     }
 
+    // --- Stall watchdog ---
+    // Turns silent stalls into something the user can act on, and stops the
+    // camera after SCAN_TIMEOUT_MS without packets to save battery.
+    function checkStall() {
+      if (!scanning) return;
+      const idle = Date.now() - lastActivityTime;
+      if (idle >= CONFIG.SCAN_TIMEOUT_MS) {
+        stopOnTimeout();
+      } else if (rejectsSinceAccept >= CONFIG.REJECT_HINT_COUNT) {
+        statusEl.textContent = 'Frames rejected — the sender may have switched data. Tap Reset.';
+      } else if (firstPacketTime && idle >= CONFIG.STALL_HINT_MS) {
+        statusEl.textContent = `No new packets for ${Math.floor(idle / 1000)}s — hold steady, move closer, or lower the sender FPS.`;
+      }
+    }
+
+    function startWatchdog() {
+      lastActivityTime   = Date.now();
+      rejectsSinceAccept = 0;
+      if (watchdogInterval) return;
+      watchdogInterval = setInterval(checkStall, 1000);
+    }
+
+    function stopWatchdog() {
+      if (watchdogInterval) {
+        clearInterval(watchdogInterval);
+        watchdogInterval = null;
+      }
+    }
+
+    // Zero partly decoded plaintext before abandoning a decoder: solved blocks
+    // are views into its data buffer, and pending packets hold XOR payloads.
+    // Must run before decoder.cancel(), which drops these references.
+    function scrubDecoder() {
+      for (const block of lastProgress?.blocks?.values() ?? []) block.fill(0);
+      for (const packets of decoder?.packets?.values() ?? []) {
+        for (const packet of packets) packet.payload.fill(0);
+      }
+      decoder?.data?.fill(0);
+    }
+
+    function stopOnTimeout() {
+      scanning = false;
+      stopSpeedTracking();
+      stopWatchdog();
+      scrubDecoder();
+      if (decoder) decoder.cancel();
+      stopCamera();
+      qramPerf.sessionEnd(sessionResult('timeout'));
+      statusEl.textContent = `Stopped after ${Math.round(CONFIG.SCAN_TIMEOUT_MS / 60000)} min without packets. Tap Reset to scan again.`;
+    }
+
+    // --- Session results ---
+    // Classify a qram.Decoder.enqueue() rejection. Partially occluded codes
+    // can scan as truncated payloads; the packet parser rejects those on its
+    // length checks rather than zero-padding them into the XOR.
+    function rejectReason(err, packet) {
+      const msg = err?.message || '';
+      // Whole-file digest failure, thrown from the enqueue that completes.
+      if (/^Decoding failed/.test(msg))                    return 'file-checksum';
+      if (err?.name === 'AbortError')                      return 'after-end';
+      if (/checksum/i.test(msg))                           return 'checksum';
+      if (/currently decoding/i.test(msg))                 return 'other-transfer';
+      // Header reads past the end of the packet, or shorter than the minimum.
+      if (err instanceof RangeError || /must be at least/.test(msg)) return 'truncated';
+      const payload = /Block size \((\d+)\) does not match packet payload size \((\d+)\)/.exec(msg);
+      if (payload) return +payload[2] < +payload[1] ? 'truncated' : 'oversized';
+      const expected = /expected (\d+)/.exec(msg);
+      if (expected) return packet.length < +expected[1] ? 'truncated' : 'oversized';
+      return 'invalid';
+    }
+
+    function sessionResult(endReason) {
+      return {
+        endReason,
+        packetsScanned,
+        totalBytesReceived,
+        blocksReceived: lastReceivedBlocks,
+        totalBlocks:    lastTotalBlocks,
+        // Packets used per source block (1.0 = no redundancy needed)
+        overheadRatio:  lastTotalBlocks > 0 ? +(packetsScanned / lastTotalBlocks).toFixed(2) : null,
+        packetsRejected: Object.values(rejectCounts).reduce((a, b) => a + b, 0),
+        rejectCounts:    { ...rejectCounts },
+        throughput:      throughputBreakdown(),
+      };
+    }
+
+    // Where the received bytes went: payload delivered versus qram header,
+    // last-block padding and packets beyond one per block (redundancy).
+    function throughputBreakdown() {
+      if (!lastProgress || !firstPacketTime) return null;
+      const { blockSize, totalSize } = lastProgress;
+      // First to last accepted packet, so idle time before a timeout or the
+      // rest of a recorded video doesn't dilute the rates.
+      const elapsed      = (lastActivityTime - firstPacketTime) / 1000;
+      const timed        = packetsScanned >= 2 && elapsed > 0;  // one packet has no duration
+      const payloadBytes = packetsScanned * blockSize;
+      const blockBytes   = lastTotalBlocks * blockSize;
+      const delivered    = Math.min(totalSize, lastReceivedBlocks * blockSize);
+      return {
+        seconds:        +elapsed.toFixed(1),
+        rawBps:         timed ? Math.round(totalBytesReceived / elapsed) : null,
+        goodputBps:     timed ? Math.round(delivered / elapsed) : null,
+        headerBytes:    totalBytesReceived - payloadBytes,
+        paddingBytes:   blockBytes - totalSize,
+        redundantBytes: Math.max(0, payloadBytes - blockBytes),
+      };
+    }
+
     // Initialize
     async function init() {
+      startDecoder('camera');
+      await initCamera();
+    }
+
+    function startDecoder(source) {
       scanning = true;
-      qramPerf.sessionStart('decode', {
-        cropFraction: CONFIG.CROP_FRACTION,
-        downscalePx:  CONFIG.DOWNSCALE_PX,
-      });
+      startWatchdog();
+      qramPerf.sessionStart('decode', source === 'video'
+        ? { source, videoScanPx: CONFIG.VIDEO_SCAN_PX, playbackRate: CONFIG.VIDEO_PLAYBACK_RATE }
+        : { source, cropFraction: CONFIG.CROP_FRACTION, downscalePx: CONFIG.DOWNSCALE_PX });
 
       decoder = new qram.Decoder();
       decodePromise = decoder.decode();
@@ -572,8 +932,6 @@
           showError('Decode error: ' + err.message);
         }
       });
-
-      await initCamera();
     }
 
     // Initialize camera
@@ -604,13 +962,82 @@
         qramScan.scheduleFrame(video, () => scanFrame(gen));
       } catch (err) {
         scanning = false;
+        stopWatchdog();
         if (decoder) decoder.cancel();
+        qramPerf.sessionEnd(sessionResult('camera-error'));
         setDecodeView(true);
         showError('Camera access denied. Please allow camera permissions.');
         console.error(err);
       }
     }
 
+    // Initialize recorded-video input — e.g. a screen recording of the sender.
+    // Same scan loop as the camera; playback is slowed so the decoder sees
+    // more of the recorded frames.
+    async function initVideoFile(file) {
+      const url = videoFileUrl = URL.createObjectURL(file);
+      video.srcObject = null;
+      video.muted = true;
+      video.src = url;
+      videoContainer.classList.add('from-file');
+      setDecodeView(true);
+
+      try {
+        await new Promise((resolve, reject) => {
+          video.addEventListener('loadedmetadata', resolve, { once: true });
+          video.addEventListener('error', reject, { once: true });
+        });
+        if (videoFileUrl !== url) return;  // reset while loading
+        video.playbackRate = CONFIG.VIDEO_PLAYBACK_RATE;
+        video.onended = onVideoEnded;
+        await video.play();
+        if (videoFileUrl !== url) return;
+        statusEl.textContent = 'Scanning video...';
+
+        const gen = ++scanGen;
+        qramScan.scheduleFrame(video, () => scanFrame(gen));
+      } catch (err) {
+        if (videoFileUrl !== url) return;
+        scanning = false;
+        stopWatchdog();
+        if (decoder) decoder.cancel();
+        stopCamera();
+        qramPerf.sessionEnd(sessionResult('video-error'));
+        showError('Could not play this video file.');
+        console.error(err);
+      }
+    }
+
+    function onVideoEnded() {
+      if (!scanning) return;
+      scanning = false;
+      stopSpeedTracking();
+      stopWatchdog();
+      scrubDecoder();
+      if (decoder) decoder.cancel();
+      qramPerf.sessionEnd(sessionResult('video-ended'));
+      statusEl.textContent = lastTotalBlocks > 0
+        ? `Video ended at ${lastReceivedBlocks}/${lastTotalBlocks} blocks — try a longer recording.`
+        : 'Video ended without any QRAM packets.';
+    }
+
+    // Draw the next frame to scan onto the offscreen canvas. The camera uses
+    // the centred scan-region crop; a recorded video is scanned whole (the
+    // code can be anywhere in a screen recording), aspect preserved.
+    function captureFrame() {
+      if (!videoFileUrl) return qramScan.cropCapture(video, canvas, ctx);
+      const vw = video.videoWidth, vh = video.videoHeight;
+      const scale = Math.min(1, CONFIG.VIDEO_SCAN_PX / Math.max(vw, vh));
+      const width  = Math.max(1, Math.round(vw * scale));
+      const height = Math.max(1, Math.round(vh * scale));
+      if (canvas.width !== width || canvas.height !== height) {
+        canvas.width  = width;
+        canvas.height = height;
+      }
+      ctx.drawImage(video, 0, 0, width, height);
+      return { width, height };
+    }
+
     function showError(msg) {
       errorMsg.textContent = msg;
       errorMsg.classList.add('show');
@@ -626,23 +1053,88 @@
       flashTimeout = setTimeout(() => scanIndicator.classList.remove('flash'), CONFIG.FLASH_DURATION_MS);
     }
 
+    // Estimated packets still needed. LT decoding typically completes after
+    // about DECODE_OVERHEAD × k packets, but the block count stalls near the
+    // end while pending packets wait for a cascade — so past that point,
+    // assume roughly one more packet per missing block rather than zero.
+    function estimatePacketsRemaining() {
+      if (lastTotalBlocks === 0) return null;
+      const missing  = lastTotalBlocks - lastReceivedBlocks;
+      const expected = Math.ceil(lastTotalBlocks * CONFIG.DECODE_OVERHEAD);
+      return Math.max(expected - packetsScanned, missing);
+    }
+
+    function formatEta(packetsRemaining) {
+      if (!firstPacketTime || !packetsRemaining) return '';
+      const elapsed = (Date.now() - firstPacketTime) / 1000;
+      if (elapsed < 0.5 || packetsScanned === 0) return '';
+      const secs = Math.ceil(packetsRemaining / (packetsScanned / elapsed));
+      return secs < 60 ? ` — ~${secs}s left` : ` — ~${Math.ceil(secs / 60)}m left`;
+    }
+
     function scheduleProgressUpdate() {
       if (pendingProgressUpdate) return;
       pendingProgressUpdate = true;
       requestAnimationFrame(() => {
         pendingProgressUpdate = false;
-        const minFrames = lastTotalBlocks > 0 ? Math.ceil(lastTotalBlocks * 1.2) : '?';
-        packetsScannedEl.textContent = `${packetsScanned} / ${minFrames}`;
+        const remaining = estimatePacketsRemaining();
+        const estFrames = remaining === null ? '?' : packetsScanned + remaining;
+        packetsScannedEl.textContent = `${packetsScanned} / ${estFrames}`;
         blocksReceivedEl.textContent = lastReceivedBlocks;
         blocksTotalEl.textContent    = lastTotalBlocks;
         const pct = lastTotalBlocks > 0
           ? Math.min(100, (lastReceivedBlocks / lastTotalBlocks) * 100)
           : 0;
         progressFill.style.width = `${pct}%`;
-        statusEl.textContent = `Receiving: ${lastReceivedBlocks}/${lastTotalBlocks} blocks`;
+        drawBlockMap();
+        statusEl.textContent = `Receiving: ${lastReceivedBlocks}/${lastTotalBlocks} blocks${formatEta(remaining)}`;
       });
     }
 
+    // Block map: one cell per block, BLOCK_MAP_COLS to a row. Solved blocks
+    // are green; unsolved ones are shaded blue by how many buffered packets
+    // still reference them (qram keeps those in decoder.packets, by index).
+    function drawBlockMap() {
+      const n = lastTotalBlocks;
+      if (!n || !lastProgress) return;
+      const cols = Math.min(n, CONFIG.BLOCK_MAP_COLS);
+      const rows = Math.ceil(n / cols);
+      if (blockMap.width !== cols || blockMap.height !== rows) {
+        blockMap.width  = cols;
+        blockMap.height = rows;
+        blockMap.style.height = `${Math.min(40, rows * 10)}px`;
+      }
+
+      const pending = new Uint32Array(n);
+      let maxPending = 1;
+      for (const [index, packets] of decoder?.packets ?? []) {
+        pending[index] = packets.size;
+        if (packets.size > maxPending) maxPending = packets.size;
+      }
+
+      const css     = getComputedStyle(document.documentElement);
+      const solved  = hexToRgb(css.getPropertyValue('--accent-green'));
+      const waiting = hexToRgb(css.getPropertyValue('--accent-blue'));
+      const img = blockMapCtx.createImageData(cols, rows);
+      const px  = img.data;
+      for (let i = 0; i < n; i++) {
+        const o = i * 4;
+        if (lastProgress.blocks.has(i)) {
+          px.set(solved, o);
+          px[o + 3] = 255;
+        } else if (pending[i]) {
+          px.set(waiting, o);
+          px[o + 3] = 64 + Math.round((191 * pending[i]) / maxPending);
+        }
+      }
+      blockMapCtx.putImageData(img, 0, 0);
+    }
+
+    function hexToRgb(hex) {
+      const h = hex.trim().slice(1);
+      return [0, 2, 4].map(i => parseInt(h.slice(i, i + 2), 16));
+    }
+
     // Scan frame — aligned to unique video frames via rVFC (rAF fallback).
     // `gen` is captured from scanGen at loop-start; stale closures bail out
     // when scanGen advances (reset/reinit), preventing double loops.
@@ -652,9 +1144,10 @@
       if (video.readyState === video.HAVE_ENOUGH_DATA) {
         qramPerf.start('scan-frame');
 
-        // Crop to the visible 85% scan region and downscale to 480px.
+        // Camera: crop to the visible scan region and downscale.
+        // Video file: whole frame, longest side up to VIDEO_SCAN_PX.
         qramPerf.start('scan-crop');
-        const { width, height } = qramScan.cropCapture(video, canvas, ctx);
+        const { width, height } = captureFrame();
         const imageData = ctx.getImageData(0, 0, width, height);
         qramPerf.end('scan-crop');
 
@@ -663,10 +1156,17 @@
           formats: ['QRCode'],
           tryHarder: true,
           tryRotate: false,  // encoder is always upright
-          tryInvert: false,  // skip inversion pass
+          tryInvert: lastInverted || emptyFrames >= CONFIG.INVERT_PROBE_FRAMES,
+          maxNumberOfSymbols: 1,  // stop after the first code read
         });
         qramPerf.end('scan-decode');
         const result = results[0];
+        if (result?.isValid) {
+          emptyFrames  = 0;
+          lastInverted = !!result.isInverted;
+        } else {
+          emptyFrames++;
+        }
 
         if (result?.isValid && result.bytes.length > 0) {
           try {
@@ -682,22 +1182,38 @@
               lastPacketSignature = sig;
 
               qramPerf.start('decode-enqueue');
-              const progress = await decoder.enqueue(packetData);
+              let progress;
+              try {
+                progress = await decoder.enqueue(packetData);
+              } catch (err) {
+                qramTrace.record(packetData, { ok: false, reason: err.message });
+                const reason = rejectReason(err, packetData);
+                rejectCounts[reason] = (rejectCounts[reason] || 0) + 1;
+                if (reason === 'other-transfer') rejectsSinceAccept++;
+                throw err;
+              }
               qramPerf.end('decode-enqueue');
-              packetsScanned++;
-              totalBytesReceived += packetData.length;
-              flashIndicator();
-
+              qramTrace.record(packetData, { ok: true, progress });
+              const now = Date.now();
               if (!firstPacketTime) {
-                firstPacketTime = Date.now();
+                firstPacketTime = now;
                 startSpeedTracking();
                 hideError();
               }
+              lastActivityTime   = now;
+              rejectsSinceAccept = 0;
+              packetsScanned++;
+              totalBytesReceived += packetData.length;
+              flashIndicator();
 
               if (progress) {
+                lastProgress       = progress;
                 lastReceivedBlocks = progress.receivedBlocks;
                 lastTotalBlocks    = progress.totalBlocks;
                 scheduleProgressUpdate();
+                // onComplete runs before this continuation, so the session
+                // summary is taken here, once the completing packet is counted.
+                if (progress.done) qramPerf.sessionEnd(sessionResult('complete'));
               }
             }
           } catch (err) {
@@ -717,12 +1233,7 @@
     async function onComplete(result) {
       scanning = false;
       stopSpeedTracking();
-      qramPerf.sessionEnd({
-        packetsScanned,
-        totalBytesReceived,
-        blocksReceived: lastReceivedBlocks,
-        totalBlocks:    lastTotalBlocks,
-      });
+      stopWatchdog();
 
       let data     = result.data;
       let wireSize = data.length;
@@ -741,21 +1252,29 @@
         }
       }
 
-      if (isFileTransfer(data)) {
-        const parsed = parseFileTransfer(data);
-        if (parsed) {
-          decodedFileData = parsed;
-          handleFileResult(parsed, data.length);
-          return;
+      try {
+        if (isFileTransfer(data)) {
+          const parsed = parseFileTransfer(data);
+          if (parsed) {
+            decodedFileData = parsed;
+            handleFileResult(parsed, data.length);
+            return;
+          }
         }
-      }
 
-      handleTextResult(data);
+        handleTextResult(data);
+      } finally {
+        // Both result paths copy out of these buffers (slice / TextDecoder);
+        // zero them so transferred secrets don't linger in extra copies.
+        result.data.fill(0);
+        data.fill(0);
+      }
     }
 
     function _finishResult(statusText) {
       resultContainer.classList.add('show');
       progressFill.style.width = '100%';
+      drawBlockMap();
       statusEl.textContent     = statusText;
       triggerCompletionFeedback();
       stopCamera();
@@ -791,6 +1310,15 @@
         cameraStream.getTracks().forEach(track => track.stop());
         cameraStream = null;
       }
+      if (videoFileUrl) {
+        video.onended = null;
+        video.pause();
+        video.removeAttribute('src');
+        video.load();
+        URL.revokeObjectURL(videoFileUrl);
+        videoFileUrl = null;
+        videoContainer.classList.remove('from-file');
+      }
     }
 
     function setDecodeView(isScanning) {
@@ -818,20 +1346,33 @@
     });
 
     // Reset
-    function handleReset() {
+    function resetSession() {
       scanning = false;
       stopSpeedTracking();
+      stopWatchdog();
+      scrubDecoder();
       if (decoder) decoder.cancel();
       stopCamera();
+      qramPerf.sessionEnd(sessionResult('reset'));  // no-op once a session has ended
 
       packetsScanned = 0;
       totalBytesReceived = 0;
       lastPacketSignature = null;
+      emptyFrames = 0;
+      lastInverted = false;
       firstPacketTime = null;
       lastReceivedBlocks = 0;
       lastTotalBlocks = 0;
+      lastProgress = null;
+      // Scrub the previous result — transfers are often credentials.
+      if (decodedFileData) decodedFileData.fileData.fill(0);
       decodedFileData = null;
+      resultEl.value = '';
+      fileResultName.textContent = '';
+      fileResultSize.textContent = '';
       pendingProgressUpdate = false;
+      rejectCounts = {};
+      qramTrace.reset();
 
       resultContainer.classList.remove('show');
       textResult.style.display   = '';
@@ -840,17 +1381,33 @@
       downloadBtn.style.display  = 'none';
       saveBtn.style.display      = 'none';
       progressFill.style.width   = '0%';
+      blockMapCtx.clearRect(0, 0, blockMap.width, blockMap.height);
       blocksReceivedEl.textContent  = '0';
       blocksTotalEl.textContent     = '?';
       packetsScannedEl.textContent  = '0 / ?';
       speedDisplayEl.textContent    = '--';
       hideError();
+    }
 
+    function handleReset() {
+      resetSession();
       init();
     }
 
     resetBtn.addEventListener('click', handleReset);
 
+    // Scan a recorded video file instead of the camera
+    videoFileBtn.addEventListener('click', () => videoFileInput.click());
+
+    videoFileInput.addEventListener('change', () => {
+      const file = videoFileInput.files[0];
+      videoFileInput.value = '';
+      if (!file) return;
+      resetSession();
+      startDecoder('video');
+      initVideoFile(file);
+    });
+
     // Register service worker for offline PWA support
     if ('serviceWorker' in navigator) {
       navigator.serviceWorker.register('./sw.js').then(reg => {
@@ -882,10 +1439,17 @@
       // Pause an in-progress scan: stop camera, cancel decoder
       scanning = false;
       stopSpeedTracking();
+      stopWatchdog();
+      scrubDecoder();
       if (decoder) { decoder.cancel(); decoder = null; }
-      if (cameraStream) { cameraStream.getTracks().forEach(t => t.stop()); cameraStream = null; }
+      qramPerf.sessionEnd(sessionResult('paused'));
+      const fromFile = !!videoFileUrl;
+      stopCamera();
       video.srcObject = null;
-      statusEl.textContent = 'Scan paused. Switch back to resume.';
+      // Returning restarts the camera; a video file has to be chosen again.
+      statusEl.textContent = fromFile
+        ? 'Video scan stopped. Switch back and choose the file again to rescan.'
+        : 'Scan paused. Switch back to resume.';
     });
 
     // Decode is the default active tab — start immediately
@@ -927,3 +1491,11 @@
       perfEncBtn.addEventListener('click', () => qramPerf.download());
       perfDecBtn.addEventListener('click', () => qramPerf.download());
     }
+
+    // ── Packet trace download button ─────────────────────────────────────
+    // Only shown when ?trace=1 is active.
+    if (window.qramTrace?.isEnabled) {
+      const traceDecBtn = document.getElementById('trace-dl-dec');
+      traceDecBtn.style.display = '';
+      traceDecBtn.addEventListener('click', () => qramTrace.download());
+    }
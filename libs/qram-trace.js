@@ -0,0 +1,118 @@
+// Packet trace recorder for QRAM Tools.
+// Activate by appending ?trace=1 to the URL — zero overhead otherwise.
+// Include via: <script src="./libs/qram-trace.js"></script>
+// Access via: window.qramTrace
+//
+// Records every packet handed to the decoder, with its timestamp and whether
+// it was accepted, so a failing capture on the phone can be downloaded and
+// replayed exactly on a dev machine.
+//
+// API:
+//   qramTrace.record(packet, outcome) — log one packet ({ ok, reason?, progress? })
+//   qramTrace.download()              — download the trace JSON via qramUtils
+//   qramTrace.reset()                 — clear recorded packets
+//   qramTrace.replay(trace)           — feed a trace (object or JSON string) into
+//                                       a fresh qram.Decoder; always available
+//   qramTrace.isEnabled               — true when ?trace=1 is active
+//
+// Trace format (JSON):
+//   { version: 1, startedAt, packets: [{ t, ok, reason?, blocks?, data }] }
+//   t     — ms since the first recorded packet
+//   data  — packet bytes, base64
+window.qramTrace = (() => {
+  const enabled = new URLSearchParams(location.search).get('trace') === '1';
+
+  const TRACE_VERSION = 1;
+  const MAX_PACKETS   = 20000;  // cap to bound memory on long sessions
+
+  // ── Base64 helpers ───────────────────────────────────────────────────────
+  function toBase64(bytes) {
+    let bin = '';
+    for (let i = 0; i < bytes.length; i++) bin += String.fromCharCode(bytes[i]);
+    return btoa(bin);
+  }
+
+  function fromBase64(str) {
+    const bin = atob(str);
+    const out = new Uint8Array(bin.length);
+    for (let i = 0; i < bin.length; i++) out[i] = bin.charCodeAt(i);
+    return out;
+  }
+
+  // ── Replay ───────────────────────────────────────────────────────────────
+  /**
+   * Feed every packet of a trace, in order, into a fresh qram.Decoder.
+   * Call from the DevTools console:  await qramTrace.replay(json)
+   *
+   * @param  {object|string} trace  trace object or its JSON text
+   * @returns {Promise<{
+   *   done:      boolean,          // decoder completed
+   *   data:      Uint8Array|null,  // decoded payload when done
+   *   used:      number,           // packets fed before completion
+   *   rejected:  number,           // packets the decoder threw on
+   *   progress:  object|null       // last progress snapshot
+   * }>}
+   */
+  async function replay(trace) {
+    if (typeof trace === 'string') trace = JSON.parse(trace);
+    if (!trace || trace.version !== TRACE_VERSION || !Array.isArray(trace.packets)) {
+      throw new Error(`qram-trace: unsupported trace (expected version ${TRACE_VERSION})`);
+    }
+
+    const decoder = new qram.Decoder();
+    let result = null;
+    const decodePromise = decoder.decode().then(r => { result = r; }, () => {});
+
+    let used = 0, rejected = 0, progress = null;
+    for (const p of trace.packets) {
+      used++;
+      try {
+        progress = await decoder.enqueue(fromBase64(p.data));
+      } catch (err) {
+        rejected++;
+        if (err.name === 'AbortError') break;  // finished or failed checksum
+        continue;
+      }
+      if (progress?.done) break;
+    }
+
+    if (!progress?.done) decoder.cancel();
+    await decodePromise;
+    return { done: !!result, data: result?.data ?? null, used, rejected, progress };
+  }
+
+  if (!enabled) {
+    const noop = () => {};
+    return { isEnabled: false, record: noop, download: noop, reset: noop, replay };
+  }
+
+  let _startedAt = null;
+  let _t0        = 0;
+  const _packets = [];
+
+  function record(packet, outcome = {}) {
+    if (_packets.length >= MAX_PACKETS) return;
+    const now = performance.now();
+    if (!_packets.length) {
+      _startedAt = new Date().toISOString();
+      _t0 = now;
+    }
+    const entry = { t: +(now - _t0).toFixed(1), ok: !!outcome.ok, data: toBase64(packet) };
+    if (outcome.reason)   entry.reason = outcome.reason;
+    if (outcome.progress) entry.blocks = outcome.progress.receivedBlocks;
+    _packets.push(entry);
+  }
+
+  function download() {
+    const json = JSON.stringify({ version: TRACE_VERSION, startedAt: _startedAt, packets: _packets });
+    qramUtils.downloadJson(json, `qram-trace-${qramUtils.getTimestampStr()}.json`);
+  }
+
+  function reset() {
+    _packets.length = 0;
+    _startedAt = null;
+  }
+
+  console.log('[qramTrace] enabled — recording decoder packets. Call qramTrace.download() anytime.');
+  return { isEnabled: true, record, download, reset, replay };
+})();
@@ -1,5 +1,5 @@
 // Bump version when any cached file changes to force update
-const CACHE_NAME = 'qram-v12';
+const CACHE_NAME = 'qram-v44';
 const ASSETS = [
   './',
   './index.html',
@@ -10,6 +10,7 @@ const ASSETS = [
   './libs/qrcode.min.js',
   './libs/qram-utils.js',
   './libs/qram-perf.js',
+  './libs/qram-trace.js',
   './libs/qram-scan.js',
   './libs/zxing-wasm.js',
   './libs/zxing_reader.wasm',